@@ -60,17 +60,65 @@ use crate::time::Hertz;
 
 #[cfg(feature = "rtic")]
 mod monotonic;
+mod counter;
+mod delay;
+mod pwm_input;
+mod qei;
+
+pub use counter::{Counter, CounterMs, CounterUs, TimerExt};
+pub use delay::Delay;
+pub use pwm_input::PwmInput;
+pub use qei::{Polarity, Qei};
+
+bitflags::bitflags! {
+    /// Interrupt events
+    pub struct Event: u32 {
+        /// Timer timed out / count down ended
+        const UPDATE = 1 << 0;
+        /// Capture/compare event on channel 1
+        const C1 = 1 << 1;
+        /// Capture/compare event on channel 2
+        const C2 = 1 << 2;
+        /// Capture/compare event on channel 3
+        const C3 = 1 << 3;
+        /// Capture/compare event on channel 4
+        const C4 = 1 << 4;
+    }
+}
 
-/// Interrupt events
-pub enum Event {
-    /// Timer timed out / count down ended
-    Update,
+/// Capture/compare channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Channel 1
+    C1,
+    /// Channel 2
+    C2,
+    /// Channel 3
+    C3,
+    /// Channel 4
+    C4,
+}
+
+impl From<Channel> for Event {
+    /// Maps a channel to its capture/compare interrupt event
+    fn from(channel: Channel) -> Self {
+        match channel {
+            Channel::C1 => Event::C1,
+            Channel::C2 => Event::C2,
+            Channel::C3 => Event::C3,
+            Channel::C4 => Event::C4,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
     /// Timer is canceled
     Canceled,
+    /// No signal is present on the input capture pin
+    NoSignal,
+    /// The requested duration/frequency does not fit in the auto-reload register
+    WrongAutoReload,
 }
 
 pub struct Timer<TIM> {
@@ -157,16 +205,18 @@ impl Timer<SYST> {
 
 impl CountDownTimer<SYST> {
     /// Starts listening for an `event`
+    ///
+    /// SysTick only supports `Event::UPDATE`; other flags are ignored.
     pub fn listen(&mut self, event: Event) {
-        match event {
-            Event::Update => self.tim.enable_interrupt(),
+        if event.contains(Event::UPDATE) {
+            self.tim.enable_interrupt();
         }
     }
 
     /// Stops listening for an `event`
     pub fn unlisten(&mut self, event: Event) {
-        match event {
-            Event::Update => self.tim.disable_interrupt(),
+        if event.contains(Event::UPDATE) {
+            self.tim.disable_interrupt();
         }
     }
 
@@ -325,18 +375,34 @@ macro_rules! hal {
             }
 
             impl CountDownTimer<$TIMX> {
-                /// Starts listening for an `event`
+                /// Starts listening for the given `event`(s)
                 pub fn listen(&mut self, event: Event) {
-                    match event {
-                        Event::Update => self.tim.dier.write(|w| w.uie().set_bit()),
-                    }
+                    self.tim.dier.modify(|r, w| unsafe { w.bits(r.bits() | event.bits()) });
                 }
 
-                /// Stops listening for an `event`
+                /// Stops listening for the given `event`(s)
                 pub fn unlisten(&mut self, event: Event) {
-                    match event {
-                        Event::Update => self.tim.dier.write(|w| w.uie().clear_bit()),
-                    }
+                    self.tim.dier.modify(|r, w| unsafe { w.bits(r.bits() & !event.bits()) });
+                }
+
+                /// Starts listening for a capture/compare interrupt on a single `channel`
+                pub fn listen_channel(&mut self, channel: Channel) {
+                    self.listen(Event::from(channel));
+                }
+
+                /// Stops listening for a capture/compare interrupt on a single `channel`
+                pub fn unlisten_channel(&mut self, channel: Channel) {
+                    self.unlisten(Event::from(channel));
+                }
+
+                /// Clears the given interrupt flag(s)
+                pub fn clear_interrupt(&mut self, event: Event) {
+                    self.tim.sr.modify(|r, w| unsafe { w.bits(r.bits() & !event.bits()) });
+                }
+
+                /// Returns the set of interrupt flags that are currently pending
+                pub fn get_interrupt(&self) -> Event {
+                    Event::from_bits_truncate(self.tim.sr.read().bits())
                 }
 
                 /// Restarts the timer in count down mode with user-defined prescaler and auto-reload register
@@ -425,7 +491,8 @@ macro_rules! hal {
                 where
                     T: Into<Hertz>,
                 {
-                    let (psc, arr) = compute_arr_presc(timeout.into().raw(), self.clk.raw());
+                    let (psc, arr) = compute_arr_presc(timeout.into().raw(), self.clk.raw())
+                        .expect("timeout does not fit in the auto-reload register");
                     self.restart_raw(psc, arr);
                 }
 
@@ -461,11 +528,19 @@ macro_rules! hal {
 }
 
 #[inline(always)]
-fn compute_arr_presc(freq: u32, clock: u32) -> (u16, u16) {
+fn compute_arr_presc(freq: u32, clock: u32) -> Result<(u16, u16), Error> {
+    if freq == 0 {
+        return Err(Error::WrongAutoReload);
+    }
+
     let ticks = clock / freq;
-    let psc = u16::try_from((ticks - 1) / (1 << 16)).unwrap();
-    let arr = u16::try_from(ticks / (psc + 1) as u32).unwrap() - 1;
-    (psc, arr)
+    if ticks == 0 {
+        return Err(Error::WrongAutoReload);
+    }
+
+    let psc = u16::try_from((ticks - 1) / (1 << 16)).map_err(|_| Error::WrongAutoReload)?;
+    let arr = u16::try_from(ticks / (psc as u32 + 1) - 1).map_err(|_| Error::WrongAutoReload)?;
+    Ok((psc, arr))
 }
 
 hal! {
@@ -0,0 +1,154 @@
+//! Fixed-precision counters built on the general-purpose timers
+//!
+//! Unlike [`CountDownTimer`](super::CountDownTimer), which only accepts
+//! [`Hertz`], a [`Counter`] ticks at a fixed, compile-time frequency `FREQ`
+//! and is driven with [`fugit`] durations/instants, so timeouts can be
+//! expressed directly as e.g. `50.millis()` instead of being converted to a
+//! frequency.
+
+use core::convert::TryFrom;
+
+use fugit::{TimerDurationU32, TimerInstantU32};
+use void::Void;
+
+use super::{Error, Instance, Timer};
+use crate::pac::RCC;
+use crate::rcc::Clocks;
+use crate::time::Hertz;
+
+/// A timer that ticks at `FREQ` Hz and works with `fugit` durations/instants
+pub struct Counter<TIM, const FREQ: u32> {
+    tim: TIM,
+    clk: Hertz,
+}
+
+/// A [`Counter`] with a tick rate of 1 kHz (millisecond resolution)
+pub type CounterMs<TIM> = Counter<TIM, 1_000>;
+
+/// A [`Counter`] with a tick rate of 1 MHz (microsecond resolution)
+pub type CounterUs<TIM> = Counter<TIM, 1_000_000>;
+
+/// Extension trait for obtaining a [`Counter`] directly from a PAC timer
+pub trait TimerExt: Sized {
+    /// Creates a `Counter` that ticks at `FREQ` Hz
+    fn counter<const FREQ: u32>(self, clocks: &Clocks) -> Counter<Self, FREQ>;
+
+    /// Creates a `Counter` that ticks at 1 kHz (millisecond resolution)
+    fn counter_ms(self, clocks: &Clocks) -> CounterMs<Self> {
+        self.counter::<1_000>(clocks)
+    }
+
+    /// Creates a `Counter` that ticks at 1 MHz (microsecond resolution)
+    fn counter_us(self, clocks: &Clocks) -> CounterUs<Self> {
+        self.counter::<1_000_000>(clocks)
+    }
+}
+
+impl<TIM: Instance> TimerExt for TIM {
+    fn counter<const FREQ: u32>(self, clocks: &Clocks) -> Counter<Self, FREQ> {
+        Counter::new(self, clocks)
+    }
+}
+
+impl<TIM: Instance, const FREQ: u32> Counter<TIM, FREQ> {
+    /// Wraps a raw peripheral into a `Counter`
+    pub fn new(tim: TIM, clocks: &Clocks) -> Self {
+        unsafe {
+            //NOTE(unsafe) this reference will only be used for atomic writes with no side effects
+            let rcc = &(*RCC::ptr());
+            TIM::enable(rcc);
+            TIM::reset(rcc);
+        }
+
+        Self {
+            clk: TIM::timer_clock(clocks),
+            tim,
+        }
+    }
+
+    /// Releases the TIM peripheral
+    pub fn release(self) -> TIM {
+        self.tim
+    }
+}
+
+/// Computes a prescaler so the timer ticks at exactly `freq` Hz, erroring if
+/// `clk` is not an exact multiple of `freq` or the prescaler does not fit 16 bits
+fn compute_psc(clk: u32, freq: u32) -> Result<u16, Error> {
+    let psc = clk / freq;
+    if psc == 0 || clk % freq != 0 {
+        return Err(Error::WrongAutoReload);
+    }
+    u16::try_from(psc - 1).map_err(|_| Error::WrongAutoReload)
+}
+
+macro_rules! counter_hal {
+    ($($TIMX:ty,)+) => {
+        $(
+            impl<const FREQ: u32> Counter<$TIMX, FREQ> {
+                /// Starts the counter, timing out after `duration`
+                pub fn start(&mut self, duration: TimerDurationU32<FREQ>) -> Result<(), Error> {
+                    // pause
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+
+                    let psc = compute_psc(self.clk.raw(), FREQ)?;
+                    let arr = u16::try_from(duration.ticks().saturating_sub(1))
+                        .map_err(|_| Error::WrongAutoReload)?;
+
+                    self.tim.psc.write(|w| w.psc().bits(psc));
+                    #[allow(unused_unsafe)]
+                    self.tim.arr.write(|w| unsafe { w.arr().bits(arr) });
+
+                    // Sets the URS bit to prevent an interrupt from being triggered by
+                    // the UG bit, then load the new psc/arr values immediately
+                    self.tim.cr1.modify(|_, w| w.urs().set_bit());
+                    self.tim.egr.write(|w| w.ug().set_bit());
+                    self.tim.cr1.modify(|_, w| w.urs().clear_bit());
+
+                    self.tim.cr1.modify(|_, w| w.cen().set_bit());
+                    Ok(())
+                }
+
+                /// Returns the current time
+                pub fn now(&self) -> TimerInstantU32<FREQ> {
+                    TimerInstantU32::from_ticks(self.tim.cnt.read().cnt().bits() as u32)
+                }
+
+                /// Returns `Ok(())` once the counter has wrapped, clearing the update flag
+                pub fn wait(&mut self) -> nb::Result<(), Void> {
+                    if self.tim.sr.read().uif().bit_is_clear() {
+                        Err(nb::Error::WouldBlock)
+                    } else {
+                        self.tim.sr.modify(|_, w| w.uif().clear_bit());
+                        Ok(())
+                    }
+                }
+
+                /// Stops the counter
+                pub fn cancel(&mut self) -> Result<(), Error> {
+                    if !self.tim.cr1.read().cen().is_enabled() {
+                        return Err(Error::Canceled);
+                    }
+
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    Ok(())
+                }
+            }
+        )+
+    }
+}
+
+#[cfg(any(feature = "stm32f100", feature = "stm32f103", feature = "connectivity",))]
+counter_hal! {
+    crate::pac::TIM1,
+}
+
+counter_hal! {
+    crate::pac::TIM2,
+    crate::pac::TIM3,
+}
+
+#[cfg(feature = "medium")]
+counter_hal! {
+    crate::pac::TIM4,
+}
@@ -0,0 +1,138 @@
+//! Blocking delays backed by the general-purpose timers
+//!
+//! SysTick (see [`Timer::syst`](super::Timer::syst)) is a single, shared
+//! resource that RTIC's monotonic timer and similar uses may need for
+//! themselves. `Delay<TIM>` provides the same blocking delay on top of
+//! TIM1–TIM4 instead, so several independent delay sources can coexist.
+//! The delay traits are not implemented for the other timers.
+
+use core::convert::TryFrom;
+
+use crate::hal::blocking::delay::{DelayMs, DelayUs};
+
+use super::{Instance, Timer};
+use crate::time::Hertz;
+
+/// Blocking delay provider, counting down on the underlying timer
+pub struct Delay<TIM> {
+    tim: TIM,
+    clk: Hertz,
+}
+
+impl<TIM: Instance> Timer<TIM> {
+    /// Creates a blocking delay provider from this timer
+    pub fn delay(self) -> Delay<TIM> {
+        Delay::new(self)
+    }
+}
+
+impl<TIM: Instance> Delay<TIM> {
+    fn new(timer: Timer<TIM>) -> Self {
+        let Timer { tim, clk } = timer;
+        Self { tim, clk }
+    }
+
+    /// Releases the TIM peripheral as a `Timer`
+    pub fn release(self) -> Timer<TIM> {
+        Timer {
+            tim: self.tim,
+            clk: self.clk,
+        }
+    }
+}
+
+macro_rules! delay_hal {
+    ($($TIMX:ty,)+) => {
+        $(
+            impl DelayUs<u32> for Delay<$TIMX> {
+                fn delay_us(&mut self, us: u32) {
+                    // pause
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+
+                    // Tick at 1 MHz, or the largest divisor of the timer clock that fits
+                    let psc = (self.clk.raw() / 1_000_000).max(1) - 1;
+                    let psc = u16::try_from(psc).unwrap_or(u16::MAX);
+                    self.tim.psc.write(|w| w.psc().bits(psc));
+
+                    let mut ticks_left = us;
+                    while ticks_left > 0 {
+                        let chunk = ticks_left.min(0xFFFF);
+                        ticks_left -= chunk;
+
+                        let arr = u16::try_from(chunk.max(1) - 1).unwrap();
+                        #[allow(unused_unsafe)]
+                        self.tim.arr.write(|w| unsafe { w.arr().bits(arr) });
+
+                        // Load the new psc/arr values and clear any stale update flag,
+                        // without triggering an interrupt, before (re)starting the counter
+                        self.tim.cr1.modify(|_, w| w.urs().set_bit());
+                        self.tim.egr.write(|w| w.ug().set_bit());
+                        self.tim.cr1.modify(|_, w| w.urs().clear_bit());
+                        self.tim.sr.modify(|_, w| w.uif().clear_bit());
+
+                        self.tim.cr1.modify(|_, w| w.cen().set_bit());
+                        while self.tim.sr.read().uif().bit_is_clear() {}
+                        self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    }
+                }
+            }
+
+            impl DelayMs<u32> for Delay<$TIMX> {
+                fn delay_ms(&mut self, ms: u32) {
+                    self.delay_us(ms.saturating_mul(1_000));
+                }
+            }
+        )+
+    }
+}
+
+#[cfg(any(feature = "stm32f100", feature = "stm32f103", feature = "connectivity",))]
+delay_hal! {
+    crate::pac::TIM1,
+}
+
+delay_hal! {
+    crate::pac::TIM2,
+    crate::pac::TIM3,
+}
+
+#[cfg(feature = "medium")]
+delay_hal! {
+    crate::pac::TIM4,
+}
+
+impl<TIM> DelayUs<u16> for Delay<TIM>
+where
+    Self: DelayUs<u32>,
+{
+    fn delay_us(&mut self, us: u16) {
+        self.delay_us(us as u32)
+    }
+}
+
+impl<TIM> DelayUs<u8> for Delay<TIM>
+where
+    Self: DelayUs<u32>,
+{
+    fn delay_us(&mut self, us: u8) {
+        self.delay_us(us as u32)
+    }
+}
+
+impl<TIM> DelayMs<u16> for Delay<TIM>
+where
+    Self: DelayMs<u32>,
+{
+    fn delay_ms(&mut self, ms: u16) {
+        self.delay_ms(ms as u32)
+    }
+}
+
+impl<TIM> DelayMs<u8> for Delay<TIM>
+where
+    Self: DelayMs<u32>,
+{
+    fn delay_ms(&mut self, ms: u8) {
+        self.delay_ms(ms as u32)
+    }
+}
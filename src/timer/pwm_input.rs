@@ -0,0 +1,115 @@
+//! # PWM Input
+//!
+//! Measures the period and duty cycle of an external PWM signal using a
+//! general-purpose timer's input capture channels.
+
+use super::sealed::{Ch1, Ch2, Remap};
+use super::{Error, Hertz, Timer};
+
+/// PWM input driver
+///
+/// The signal to be measured is applied to the timer's CH1 pin. TI1 is
+/// routed to both capture/compare channels: CC1 captures on the rising edge
+/// (giving the signal's period) and CC2 captures on the falling edge (giving
+/// its high time). The slave mode controller resets the counter on every
+/// rising edge of TI1, so both captures are relative to the start of the
+/// period.
+pub struct PwmInput<TIM> {
+    tim: TIM,
+    clk: Hertz,
+}
+
+macro_rules! pwm_input_hal {
+    ($($TIMX:ty,)+) => {
+        $(
+            impl Timer<$TIMX> {
+                /// Configures the timer to measure the period and duty cycle of the
+                /// PWM signal applied to the `Ch1`/`Ch2` pin pair
+                pub fn pwm_input<REMAP, P1, P2>(self, _pins: (P1, P2)) -> PwmInput<$TIMX>
+                where
+                    REMAP: Remap<Periph = $TIMX>,
+                    P1: Ch1<REMAP>,
+                    P2: Ch2<REMAP>,
+                {
+                    PwmInput::new(self)
+                }
+            }
+
+            impl PwmInput<$TIMX> {
+                fn new(timer: Timer<$TIMX>) -> Self {
+                    let Timer { tim, clk } = timer;
+
+                    // Map TI1 to both CC1 (rising edge, full period) and CC2 (falling
+                    // edge, high time)
+                    tim.ccmr1_input()
+                        .write(|w| unsafe { w.cc1s().bits(0b01).cc2s().bits(0b10) });
+                    tim.ccer.write(|w| w.cc1p().clear_bit().cc2p().set_bit());
+
+                    // Reset the counter on every rising edge of TI1FP1
+                    tim.smcr
+                        .write(|w| unsafe { w.ts().bits(0b101).sms().bits(0b100) });
+
+                    tim.ccer
+                        .modify(|_, w| w.cc1e().set_bit().cc2e().set_bit());
+                    tim.cr1.modify(|_, w| w.cen().set_bit());
+
+                    Self { tim, clk }
+                }
+
+                /// Returns the frequency of the measured signal
+                ///
+                /// Returns `Error::NoSignal` if no rising edge has been captured yet
+                /// (CCR1 reads zero).
+                pub fn read_frequency(&self) -> Result<Hertz, Error> {
+                    let ccr1 = self.tim.ccr1.read().ccr().bits();
+                    if ccr1 == 0 {
+                        return Err(Error::NoSignal);
+                    }
+
+                    let psc = self.tim.psc.read().psc().bits() as u32;
+                    Ok(Hertz::from_raw(self.clk.raw() / (psc as u32 + 1) / ccr1 as u32))
+                }
+
+                /// Returns the `(high_time, period)` pair of raw capture counts for the
+                /// measured signal
+                ///
+                /// The duty cycle as a ratio is `high_time as f32 / period as f32`.
+                /// Returns `Error::NoSignal` if no rising edge has been captured yet
+                /// (CCR1 reads zero).
+                pub fn read_duty(&self) -> Result<(u16, u16), Error> {
+                    let ccr1 = self.tim.ccr1.read().ccr().bits();
+                    if ccr1 == 0 {
+                        return Err(Error::NoSignal);
+                    }
+
+                    let ccr2 = self.tim.ccr2.read().ccr().bits();
+                    Ok((ccr2, ccr1))
+                }
+
+                /// Stops the timer and releases it
+                pub fn release(self) -> Timer<$TIMX> {
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    Timer {
+                        tim: self.tim,
+                        clk: self.clk,
+                    }
+                }
+            }
+        )+
+    }
+}
+
+#[cfg(any(feature = "stm32f100", feature = "stm32f103", feature = "connectivity",))]
+pwm_input_hal! {
+    crate::pac::TIM1,
+}
+
+pwm_input_hal! {
+    crate::pac::TIM2,
+    crate::pac::TIM3,
+}
+
+#[cfg(feature = "medium")]
+pwm_input_hal! {
+    crate::pac::TIM4,
+}
@@ -0,0 +1,139 @@
+//! # Quadrature Encoder Interface (QEI)
+//!
+//! Reads a rotary quadrature encoder connected to a timer's `Ch1`/`Ch2` pin
+//! pair, using the timer's slave mode controller in encoder mode.
+
+use crate::hal::Direction;
+
+use super::sealed::{Ch1, Ch2, Remap};
+use super::Timer;
+use crate::time::Hertz;
+
+/// QEI driver
+pub struct Qei<TIM> {
+    tim: TIM,
+    clk: Hertz,
+}
+
+/// Input polarity for a QEI channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    /// Not inverted: the input is used as-is
+    NotInverted,
+    /// Inverted: the input is inverted before being fed to the counter
+    Inverted,
+}
+
+macro_rules! qei_hal {
+    ($($TIMX:ty,)+) => {
+        $(
+            impl Timer<$TIMX> {
+                /// Configures the timer as a quadrature encoder interface, reading the
+                /// signal applied to the `Ch1`/`Ch2` pin pair
+                ///
+                /// Both channels are configured with non-inverted input polarity; use
+                /// [`Timer::qei_with_polarity`] to configure inverted inputs instead.
+                pub fn qei<REMAP, P1, P2>(self, pins: (P1, P2)) -> Qei<$TIMX>
+                where
+                    REMAP: Remap<Periph = $TIMX>,
+                    P1: Ch1<REMAP>,
+                    P2: Ch2<REMAP>,
+                {
+                    self.qei_with_polarity(pins, Polarity::NotInverted, Polarity::NotInverted)
+                }
+
+                /// Configures the timer as a quadrature encoder interface, as with
+                /// [`Timer::qei`], but with configurable input polarity for each channel
+                pub fn qei_with_polarity<REMAP, P1, P2>(
+                    self,
+                    _pins: (P1, P2),
+                    ch1_polarity: Polarity,
+                    ch2_polarity: Polarity,
+                ) -> Qei<$TIMX>
+                where
+                    REMAP: Remap<Periph = $TIMX>,
+                    P1: Ch1<REMAP>,
+                    P2: Ch2<REMAP>,
+                {
+                    Qei::new(self, ch1_polarity, ch2_polarity)
+                }
+            }
+
+            impl Qei<$TIMX> {
+                fn new(timer: Timer<$TIMX>, ch1_polarity: Polarity, ch2_polarity: Polarity) -> Self {
+                    let Timer { tim, clk } = timer;
+
+                    // Configure CC1 and CC2 as inputs mapped to TI1 and TI2 respectively,
+                    // with no input filtering and the requested input polarity
+                    tim.ccmr1_input()
+                        .write(|w| unsafe { w.cc1s().bits(0b01).cc2s().bits(0b01) });
+                    tim.ccer.write(|w| {
+                        w.cc1p().bit(ch1_polarity == Polarity::Inverted);
+                        w.cc2p().bit(ch2_polarity == Polarity::Inverted)
+                    });
+
+                    // Encoder mode 3: count on both TI1 and TI2 edges
+                    tim.smcr.write(|w| unsafe { w.sms().bits(0b011) });
+
+                    // TODO: Remove this `allow` once this field is made safe for stm32f100
+                    #[allow(unused_unsafe)]
+                    tim.arr.write(|w| unsafe { w.arr().bits(0xFFFF) });
+
+                    tim.cr1.modify(|_, w| w.cen().set_bit());
+
+                    Self { tim, clk }
+                }
+
+                /// Returns the current counter value
+                pub fn count(&self) -> u16 {
+                    self.tim.cnt.read().cnt().bits()
+                }
+
+                /// Returns the counting direction as observed at the last counter update
+                pub fn direction(&self) -> Direction {
+                    if self.tim.cr1.read().dir().bit_is_clear() {
+                        Direction::Upcounting
+                    } else {
+                        Direction::Downcounting
+                    }
+                }
+
+                /// Releases the timer peripheral
+                pub fn release(self) -> Timer<$TIMX> {
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    Timer {
+                        tim: self.tim,
+                        clk: self.clk,
+                    }
+                }
+            }
+
+            impl crate::hal::Qei for Qei<$TIMX> {
+                type Count = u16;
+
+                fn count(&self) -> u16 {
+                    Qei::count(self)
+                }
+
+                fn direction(&self) -> Direction {
+                    Qei::direction(self)
+                }
+            }
+        )+
+    }
+}
+
+#[cfg(any(feature = "stm32f100", feature = "stm32f103", feature = "connectivity",))]
+qei_hal! {
+    crate::pac::TIM1,
+}
+
+qei_hal! {
+    crate::pac::TIM2,
+    crate::pac::TIM3,
+}
+
+#[cfg(feature = "medium")]
+qei_hal! {
+    crate::pac::TIM4,
+}